@@ -1,16 +1,115 @@
 mod ntfy;
 
+use std::sync::atomic::Ordering;
 use tauri::{
-    menu::{Menu, MenuItem},
+    menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    Manager, WindowEvent,
+    AppHandle, Manager, WindowEvent,
 };
 
+// ntfy priorities run 1 (min) ..= 5 (max); offered in the per-subscription
+// tray submenu so min_priority can be set without the main window.
+const PRIORITY_LEVELS: [i32; 5] = [1, 2, 3, 4, 5];
+
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let is_visible = window.is_visible().unwrap_or(false);
+        if is_visible {
+            window.hide().unwrap();
+        } else {
+            window.show().unwrap();
+            window.set_focus().unwrap();
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                ntfy::clear_unread(&app).await;
+            });
+        }
+    }
+}
+
+/// Builds the per-subscription submenu: a mute checkbox plus a min-priority
+/// picker, so both can be changed from the tray without opening the window.
+fn build_subscription_submenu(
+    app: &AppHandle,
+    full_url: &str,
+    sub: &ntfy::Subscription,
+    muted: bool,
+) -> tauri::Result<Submenu<tauri::Wry>> {
+    let mute_i = CheckMenuItem::with_id(
+        app,
+        format!("mute:{}", full_url),
+        "Enabled",
+        true,
+        !muted,
+        None::<&str>,
+    )?;
+
+    let mut items: Vec<Box<dyn IsMenuItem<tauri::Wry>>> = vec![Box::new(mute_i)];
+    items.push(Box::new(PredefinedMenuItem::separator(app)?));
+
+    for level in PRIORITY_LEVELS {
+        let item = CheckMenuItem::with_id(
+            app,
+            format!("priority:{}:{}", full_url, level),
+            format!("Min priority {}", level),
+            true,
+            sub.min_priority.load(Ordering::Relaxed) == level,
+            None::<&str>,
+        )?;
+        items.push(Box::new(item));
+    }
+
+    let refs: Vec<&dyn IsMenuItem<tauri::Wry>> = items.iter().map(|i| i.as_ref()).collect();
+    Submenu::with_items(app, full_url, true, &refs)
+}
+
+/// Rebuilds the tray menu from the current subscriptions, so the mute
+/// checkboxes and min-priority picker stay in sync with
+/// `subscribe`/`unsubscribe`/`set_min_priority`.
+pub(crate) async fn rebuild_tray_menu(app: &AppHandle) {
+    let state = app.state::<ntfy::NtfyState>();
+    let subs = state.subscriptions.lock().await;
+    let muted = state.muted.lock().await;
+
+    let Ok(toggle_i) = MenuItem::with_id(app, "toggle", "Toggle Window", true, None::<&str>) else {
+        return;
+    };
+    let Ok(quit_i) = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>) else {
+        return;
+    };
+
+    let mut items: Vec<Box<dyn IsMenuItem<tauri::Wry>>> = vec![Box::new(toggle_i)];
+
+    if !subs.is_empty() {
+        if let Ok(sep) = PredefinedMenuItem::separator(app) {
+            items.push(Box::new(sep));
+        }
+        for (full_url, sub) in subs.iter() {
+            let is_muted = muted.contains(full_url);
+            if let Ok(submenu) = build_subscription_submenu(app, full_url, sub, is_muted) {
+                items.push(Box::new(submenu));
+            }
+        }
+    }
+
+    if let Ok(sep) = PredefinedMenuItem::separator(app) {
+        items.push(Box::new(sep));
+    }
+    items.push(Box::new(quit_i));
+
+    let refs: Vec<&dyn IsMenuItem<tauri::Wry>> = items.iter().map(|i| i.as_ref()).collect();
+    if let Ok(menu) = Menu::with_items(app, &refs) {
+        if let Some(tray) = state.tray.lock().await.as_ref() {
+            let _ = tray.set_menu(Some(menu));
+        }
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -23,26 +122,37 @@ pub fn run() {
             let toggle_i = MenuItem::with_id(app, "toggle", "Toggle Window", true, None::<&str>)?;
             let menu = Menu::with_items(app, &[&toggle_i, &quit_i])?;
 
+            ntfy::restore_subscriptions(app.handle());
+
             // 트레이 아이콘 설정 (아이콘은 기본 앱 아이콘 사용)
-            let _tray = TrayIconBuilder::new()
+            let tray = TrayIconBuilder::new()
                 .menu(&menu)
                 .icon(app.default_window_icon().unwrap().clone())
-                .on_menu_event(|app, event| match event.id.as_ref() {
-                    "quit" => {
+                .on_menu_event(|app, event| {
+                    let id = event.id.as_ref();
+                    if id == "quit" {
                         std::process::exit(0);
-                    }
-                    "toggle" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let is_visible = window.is_visible().unwrap_or(false);
-                            if is_visible {
-                                window.hide().unwrap();
-                            } else {
-                                window.show().unwrap();
-                                window.set_focus().unwrap();
+                    } else if id == "toggle" {
+                        show_main_window(app);
+                    } else if let Some(full_url) = id.strip_prefix("mute:") {
+                        let app = app.clone();
+                        let full_url = full_url.to_string();
+                        tauri::async_runtime::spawn(async move {
+                            ntfy::toggle_mute(&app, &full_url).await;
+                            rebuild_tray_menu(&app).await;
+                        });
+                    } else if let Some(rest) = id.strip_prefix("priority:") {
+                        if let Some((full_url, level)) = rest.rsplit_once(':') {
+                            if let Ok(min_priority) = level.parse::<i32>() {
+                                let app = app.clone();
+                                let full_url = full_url.to_string();
+                                tauri::async_runtime::spawn(async move {
+                                    ntfy::set_min_priority(&app, &full_url, min_priority).await;
+                                    rebuild_tray_menu(&app).await;
+                                });
                             }
                         }
                     }
-                    _ => {}
                 })
                 .on_tray_icon_event(|tray, event| {
                     if let TrayIconEvent::Click {
@@ -51,20 +161,13 @@ pub fn run() {
                         ..
                     } = event
                     {
-                        let app = tray.app_handle();
-                        if let Some(window) = app.get_webview_window("main") {
-                            let is_visible = window.is_visible().unwrap_or(false);
-                            if is_visible {
-                                window.hide().unwrap();
-                            } else {
-                                window.show().unwrap();
-                                window.set_focus().unwrap();
-                            }
-                        }
+                        show_main_window(tray.app_handle());
                     }
                 })
                 .build(app)?;
 
+            app.state::<ntfy::NtfyState>().tray.blocking_lock().replace(tray);
+
             Ok(())
         })
         .on_window_event(|window, event| match event {
@@ -75,7 +178,13 @@ pub fn run() {
             }
             _ => {}
         })
-        .invoke_handler(tauri::generate_handler![greet, ntfy::subscribe, ntfy::unsubscribe])
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            ntfy::subscribe,
+            ntfy::unsubscribe,
+            ntfy::list_subscriptions,
+            ntfy::notification_action_performed
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
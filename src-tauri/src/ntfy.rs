@@ -1,12 +1,44 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicI32, Ordering};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_opener::OpenerExt;
 use tokio::sync::Mutex;
 use futures_util::StreamExt;
 use reqwest_eventsource::{EventSource, Event};
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NtfyAction {
+    pub action: String,
+    pub label: String,
+    pub url: Option<String>,
+    pub method: Option<String>,
+    pub headers: Option<HashMap<String, String>>,
+    pub body: Option<String>,
+    #[serde(default)]
+    pub clear: bool,
+}
+
+/// Credentials for a protected/reserved ntfy topic.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type")]
+pub enum Auth {
+    Bearer { token: String },
+    Basic { user: String, pass: String },
+}
+
+impl Auth {
+    fn apply(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self {
+            Auth::Bearer { token } => builder.bearer_auth(token),
+            Auth::Basic { user, pass } => builder.basic_auth(user, Some(pass)),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct NtfyMessage {
     pub id: String,
@@ -17,28 +49,360 @@ pub struct NtfyMessage {
     pub title: Option<String>,
     pub tags: Option<Vec<String>>,
     pub priority: Option<i32>,
+    pub actions: Option<Vec<NtfyAction>>,
+}
+
+// Bounds how many message ids we remember per subscription for de-duplicating
+// `since` replays against messages that also arrive live at the reconnect
+// boundary.
+const SEEN_ID_CAPACITY: usize = 200;
+
+#[derive(Default)]
+struct SeenIds {
+    order: VecDeque<String>,
+    set: HashSet<String>,
+}
+
+impl SeenIds {
+    /// Returns `true` if `id` hadn't been seen yet (and records it).
+    fn insert(&mut self, id: &str) -> bool {
+        if !self.set.insert(id.to_string()) {
+            return false;
+        }
+        self.order.push_back(id.to_string());
+        if self.order.len() > SEEN_ID_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.set.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+// ntfy priorities run 1 (min) ..= 5 (max); this is the default when a
+// subscription doesn't request filtering, i.e. show everything.
+const DEFAULT_MIN_PRIORITY: i32 = 1;
+
+// Bounds how many notifications' action sets we keep waiting for a click,
+// mirroring `SeenIds`: a notification that's dismissed, ignored, or expires
+// without a click would otherwise sit here for the lifetime of the process.
+const PENDING_ACTIONS_CAPACITY: usize = 200;
+
+#[derive(Default)]
+struct PendingActions {
+    order: VecDeque<i32>,
+    map: HashMap<i32, Vec<NtfyAction>>,
+}
+
+impl PendingActions {
+    fn insert(&mut self, id: i32, actions: Vec<NtfyAction>) {
+        if self.map.insert(id, actions).is_none() {
+            self.order.push_back(id);
+            if self.order.len() > PENDING_ACTIONS_CAPACITY {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.map.remove(&oldest);
+                }
+            }
+        }
+    }
+
+    fn remove(&mut self, id: i32) -> Option<Vec<NtfyAction>> {
+        let actions = self.map.remove(&id);
+        if actions.is_some() {
+            self.order.retain(|&pending_id| pending_id != id);
+        }
+        actions
+    }
+}
+
+pub struct Subscription {
+    pub handle: tokio::task::JoinHandle<()>,
+    pub server_url: String,
+    pub topic: String,
+    pub auth: Option<Auth>,
+    // Shared (not just stored) so the tray's priority picker can change a
+    // threshold the spawned SSE task is already reading on every message.
+    pub min_priority: Arc<AtomicI32>,
+}
+
+/// What we persist to disk so subscriptions survive a restart.
+///
+/// Deliberately excludes `Auth`: bearer tokens and Basic-auth passwords must
+/// not sit in a plaintext file under the app data dir. Protected topics
+/// require re-entering credentials after a restart.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SavedSubscription {
+    pub server_url: String,
+    pub topic: String,
+    pub min_priority: i32,
+}
+
+fn subscriptions_file(app: &AppHandle) -> Option<PathBuf> {
+    let dir = app.path().app_data_dir().ok()?;
+    Some(dir.join("subscriptions.json"))
+}
+
+async fn load_saved_subscriptions(app: &AppHandle) -> Vec<SavedSubscription> {
+    let Some(path) = subscriptions_file(app) else {
+        return Vec::new();
+    };
+    match tokio::fs::read_to_string(&path).await {
+        Ok(contents) => serde_json::from_str(&contents).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    }
+}
+
+async fn persist_subscriptions(app: &AppHandle, subs: &HashMap<String, Subscription>) {
+    let Some(path) = subscriptions_file(app) else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = tokio::fs::create_dir_all(parent).await;
+    }
+    let saved: Vec<SavedSubscription> = subs
+        .values()
+        .map(|sub| SavedSubscription {
+            server_url: sub.server_url.clone(),
+            topic: sub.topic.clone(),
+            min_priority: sub.min_priority.load(Ordering::Relaxed),
+        })
+        .collect();
+    if let Ok(json) = serde_json::to_string_pretty(&saved) {
+        if tokio::fs::write(&path, json).await.is_ok() {
+            // Defense in depth even though the file no longer holds secrets.
+            restrict_permissions(&path).await;
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn restrict_permissions(path: &std::path::Path) {
+    use std::os::unix::fs::PermissionsExt;
+    let _ = tokio::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600)).await;
+}
+
+#[cfg(not(unix))]
+async fn restrict_permissions(_path: &std::path::Path) {}
+
+/// Reads subscriptions saved from a previous run and re-subscribes to each,
+/// so a long-running tray app doesn't forget its topics across restarts.
+/// Subscriptions that needed `Auth` come back unauthenticated; the user has
+/// to re-add credentials for those.
+pub fn restore_subscriptions(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        for saved in load_saved_subscriptions(&app).await {
+            if let Err(e) = subscribe(
+                saved.server_url,
+                saved.topic,
+                None,
+                Some(saved.min_priority),
+                app.clone(),
+            )
+            .await
+            {
+                eprintln!("failed to restore subscription: {}", e);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn list_subscriptions(app: AppHandle) -> Result<Vec<SavedSubscription>, String> {
+    let state = app.state::<NtfyState>();
+    let subs = state.subscriptions.lock().await;
+    Ok(subs
+        .values()
+        .map(|sub| SavedSubscription {
+            server_url: sub.server_url.clone(),
+            topic: sub.topic.clone(),
+            min_priority: sub.min_priority.load(Ordering::Relaxed),
+        })
+        .collect())
 }
 
 pub struct NtfyState {
-    pub subscriptions: Arc<Mutex<HashMap<String, tokio::task::JoinHandle<()>>>>,
+    pub subscriptions: Arc<Mutex<HashMap<String, Subscription>>>,
+    // notification_id -> actions, so a click on a button can be mapped back
+    // to the ntfy action that produced it.
+    pending_actions: Arc<Mutex<PendingActions>>,
+    // full_url of subscriptions the user muted from the tray menu.
+    pub muted: Arc<Mutex<HashSet<String>>>,
+    pub unread: Arc<Mutex<u32>>,
+    pub tray: Arc<Mutex<Option<tauri::tray::TrayIcon<tauri::Wry>>>>,
 }
 
 impl Default for NtfyState {
     fn default() -> Self {
         Self {
             subscriptions: Arc::new(Mutex::new(HashMap::new())),
+            pending_actions: Arc::new(Mutex::new(PendingActions::default())),
+            muted: Arc::new(Mutex::new(HashSet::new())),
+            unread: Arc::new(Mutex::new(0)),
+            tray: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+/// Flips whether `full_url` is muted; called when the tray's per-topic
+/// checkbox is clicked.
+pub async fn toggle_mute(app: &AppHandle, full_url: &str) {
+    let state = app.state::<NtfyState>();
+    let mut muted = state.muted.lock().await;
+    if !muted.insert(full_url.to_string()) {
+        muted.remove(full_url);
+    }
+}
+
+/// Sets `full_url`'s min-priority threshold; called from the tray's
+/// per-subscription priority picker. Takes effect immediately for the
+/// already-running subscription since `min_priority` is shared, not copied.
+pub async fn set_min_priority(app: &AppHandle, full_url: &str, min_priority: i32) {
+    let state = app.state::<NtfyState>();
+    let subs = state.subscriptions.lock().await;
+    if let Some(sub) = subs.get(full_url) {
+        sub.min_priority.store(min_priority, Ordering::Relaxed);
+    }
+    persist_subscriptions(app, &subs).await;
+}
+
+/// Bumps the unread counter and reflects it on the tray icon title.
+pub async fn increment_unread(app: &AppHandle) {
+    let state = app.state::<NtfyState>();
+    let mut unread = state.unread.lock().await;
+    *unread += 1;
+    if let Some(tray) = state.tray.lock().await.as_ref() {
+        let _ = tray.set_title(Some(unread.to_string()));
+    }
+}
+
+/// Clears the unread counter, e.g. once the main window is shown.
+pub async fn clear_unread(app: &AppHandle) {
+    let state = app.state::<NtfyState>();
+    *state.unread.lock().await = 0;
+    if let Some(tray) = state.tray.lock().await.as_ref() {
+        let _ = tray.set_title(None::<&str>);
+    }
+}
+
+// Linux notification backends (zbus-based) don't reliably surface action
+// buttons the way macOS/Windows toasts do, so we only offer them where the
+// OS actually advertises support instead of rendering dead buttons.
+fn platform_supports_action_buttons() -> bool {
+    cfg!(any(target_os = "macos", target_os = "windows"))
+}
+
+/// Invoked once an action button is clicked, to map it back to the
+/// originating `NtfyAction` and carry out `view`/`http`/`broadcast`.
+///
+/// The notification plugin surfaces action clicks to the webview, not to
+/// Rust directly, so this is a `#[tauri::command]` rather than an
+/// `app.emit`/`app.listen` pair: a frontend's action-click listener is
+/// expected to `invoke("notification_action_performed", ...)` with the ids
+/// the plugin hands it. This tree has no frontend yet, so nothing calls it
+/// today — it's the integration point one would wire up to.
+#[tauri::command]
+pub async fn notification_action_performed(app: AppHandle, notification_id: i32, action_id: String) {
+    dispatch_action(app, ActionPerformed { notification_id, action_id }).await;
+}
+
+struct ActionPerformed {
+    notification_id: i32,
+    action_id: String,
+}
+
+async fn dispatch_action(app: AppHandle, performed: ActionPerformed) {
+    let state = app.state::<NtfyState>();
+    let actions = {
+        let mut pending = state.pending_actions.lock().await;
+        pending.remove(performed.notification_id)
+    };
+
+    let Some(actions) = actions else { return };
+    let Some(action) = actions.into_iter().find(|a| a.action == performed.action_id || a.label == performed.action_id) else {
+        return;
+    };
+
+    match action.action.as_str() {
+        "view" => {
+            if let Some(url) = &action.url {
+                let _ = app.opener().open_url(url, None::<&str>);
+            }
+        }
+        "http" => {
+            let Some(url) = action.url.clone() else { return };
+            let method = action
+                .method
+                .clone()
+                .unwrap_or_else(|| "POST".to_string());
+            let headers = action.headers.clone().unwrap_or_default();
+            let body = action.body.clone().unwrap_or_default();
+
+            tokio::spawn(async move {
+                let client = reqwest::Client::new();
+                let method = reqwest::Method::from_bytes(method.as_bytes())
+                    .unwrap_or(reqwest::Method::POST);
+                let mut req = client.request(method, &url);
+                for (key, value) in headers {
+                    req = req.header(key, value);
+                }
+                if !body.is_empty() {
+                    req = req.body(body);
+                }
+                if let Err(e) = req.send().await {
+                    eprintln!("ntfy action http call failed: {}", e);
+                }
+            });
+        }
+        "broadcast" => {
+            let _ = app.emit("ntfy-action-broadcast", &action);
+        }
+        other => {
+            eprintln!("unknown ntfy action type: {}", other);
+        }
+    }
+
+    if action.clear {
+        match app.notification().close(performed.notification_id) {
+            Ok(_) => println!("Notification dismissed: {}", performed.notification_id),
+            Err(e) => eprintln!("failed to dismiss notification {}: {}", performed.notification_id, e),
         }
     }
 }
 
 #[tauri::command]
-pub async fn subscribe(server_url: String, topic: String, app: AppHandle) -> Result<(), String> {
+pub async fn subscribe(
+    server_url: String,
+    topic: String,
+    auth: Option<Auth>,
+    min_priority: Option<i32>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let min_priority = Arc::new(AtomicI32::new(min_priority.unwrap_or(DEFAULT_MIN_PRIORITY)));
     let state = app.state::<NtfyState>();
-    let mut subs = state.subscriptions.lock().await;
 
     let base_url = server_url.trim_end_matches('/');
     let full_url = format!("{}/{}", base_url, topic);
 
+    if state.subscriptions.lock().await.contains_key(&full_url) {
+        return Ok(());
+    }
+
+    let probe_client = reqwest::Client::new();
+    let mut probe = probe_client.get(format!("{}/sse?poll=1", full_url));
+    if let Some(auth) = &auth {
+        probe = auth.apply(probe);
+    }
+    match probe.send().await {
+        Ok(resp) if resp.status() == reqwest::StatusCode::UNAUTHORIZED
+            || resp.status() == reqwest::StatusCode::FORBIDDEN =>
+        {
+            return Err(format!("unauthorized: server returned {}", resp.status()));
+        }
+        _ => {}
+    }
+
+    let mut subs = state.subscriptions.lock().await;
     if subs.contains_key(&full_url) {
         return Ok(());
     }
@@ -46,13 +410,22 @@ pub async fn subscribe(server_url: String, topic: String, app: AppHandle) -> Res
     let full_url_clone = full_url.clone();
     let app_clone = app.clone();
     let base_url_clone = base_url.to_string();
+    let auth_clone = auth.clone();
+    let seen_ids_clone = Arc::new(Mutex::new(SeenIds::default()));
+    let min_priority_clone = min_priority.clone();
 
     let handle = tokio::spawn(async move {
-        let sse_url = format!("{}/sse", full_url_clone);
         let client = reqwest::Client::new();
+        // First connect replays everything; later reconnects replay only
+        // what we might have missed since the last event we saw.
+        let mut since = "all".to_string();
 
         loop {
-            let req = client.get(&sse_url);
+            let sse_url = format!("{}/sse?since={}", full_url_clone, since);
+            let mut req = client.get(&sse_url);
+            if let Some(auth) = &auth_clone {
+                req = auth.apply(req);
+            }
             if let Ok(mut es) = EventSource::new(req) {
                 while let Some(event) = es.next().await {
                     match event {
@@ -61,24 +434,64 @@ pub async fn subscribe(server_url: String, topic: String, app: AppHandle) -> Res
                         }
                         Ok(Event::Message(message)) => {
                             if let Ok(msg) = serde_json::from_str::<NtfyMessage>(&message.data) {
+                                since = msg.time.to_string();
+
                                 if msg.event == "message" {
+                                    let is_new = seen_ids_clone.lock().await.insert(&msg.id);
+                                    if !is_new {
+                                        continue;
+                                    }
+
                                     let title = msg.title.clone().unwrap_or_else(|| format!("ntfy: {}", msg.topic));
                                     let body = msg.message.clone().unwrap_or_default();
+                                    let priority = msg.priority.unwrap_or(3);
 
                                     // Tauri 표준 알림 플러그인 사용
                                     // 1. 알림 ID로 정수형 값을 전달 (시간 기반 하위 32비트 사용)
                                     // 2. 리눅스에서 팝업이 유지되도록 유도
                                     let notification_id = (msg.time % (i32::MAX as i64)) as i32;
 
-                                    match app_clone.notification()
-                                        .builder()
-                                        .id(notification_id)
-                                        .title(&title)
-                                        .body(&body)
-                                        .show()
-                                    {
-                                        Ok(_) => println!("Notification sent to OS: {}", msg.id),
-                                        Err(e) => eprintln!("Notification error: {}", e),
+                                    let state = app_clone.state::<NtfyState>();
+                                    let is_muted = state.muted.lock().await.contains(&full_url_clone);
+                                    let min_priority = min_priority_clone.load(Ordering::Relaxed);
+
+                                    if priority >= min_priority && !is_muted {
+                                        if let Some(actions) = &msg.actions {
+                                            if platform_supports_action_buttons() && !actions.is_empty() {
+                                                state
+                                                    .pending_actions
+                                                    .lock()
+                                                    .await
+                                                    .insert(notification_id, actions.clone());
+                                            }
+                                        }
+
+                                        let mut builder = app_clone.notification()
+                                            .builder()
+                                            .id(notification_id)
+                                            .title(&title)
+                                            .body(&body);
+
+                                        // Max and high priority demand attention and stay until
+                                        // dismissed; min priority stays passive and silent.
+                                        builder = match priority {
+                                            5 | 4 => builder.sound("default").auto_cancel(false),
+                                            1 => builder.silent(true),
+                                            _ => builder,
+                                        };
+
+                                        if platform_supports_action_buttons() {
+                                            if let Some(actions) = &msg.actions {
+                                                for action in actions {
+                                                    builder = builder.action_button(&action.action, &action.label);
+                                                }
+                                            }
+                                        }
+
+                                        match builder.show() {
+                                            Ok(_) => println!("Notification sent to OS: {}", msg.id),
+                                            Err(e) => eprintln!("Notification error: {}", e),
+                                        }
                                     }
 
                                     #[derive(Serialize, Clone)]
@@ -93,13 +506,14 @@ pub async fn subscribe(server_url: String, topic: String, app: AppHandle) -> Res
                                     };
 
                                     let _ = app_clone.emit("new-message", &payload);
+                                    increment_unread(&app_clone).await;
                                 }
                             }
                         }
                         Err(err) => {
                             println!("SSE Error for {}: {}", sse_url, err);
                             es.close();
-                            break; 
+                            break;
                         }
                     }
                 }
@@ -108,7 +522,19 @@ pub async fn subscribe(server_url: String, topic: String, app: AppHandle) -> Res
         }
     });
 
-    subs.insert(full_url, handle);
+    subs.insert(
+        full_url,
+        Subscription {
+            handle,
+            server_url: base_url.to_string(),
+            topic,
+            auth,
+            min_priority,
+        },
+    );
+    persist_subscriptions(&app, &subs).await;
+    drop(subs);
+    crate::rebuild_tray_menu(&app).await;
     Ok(())
 }
 
@@ -120,9 +546,66 @@ pub async fn unsubscribe(server_url: String, topic: String, app: AppHandle) -> R
     let base_url = server_url.trim_end_matches('/');
     let full_url = format!("{}/{}", base_url, topic);
 
-    if let Some(handle) = subs.remove(&full_url) {
-        handle.abort();
+    if let Some(sub) = subs.remove(&full_url) {
+        sub.handle.abort();
     }
+    persist_subscriptions(&app, &subs).await;
+    state.muted.lock().await.remove(&full_url);
+    drop(subs);
+    crate::rebuild_tray_menu(&app).await;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seen_ids_insert_reports_new_vs_seen() {
+        let mut seen = SeenIds::default();
+        assert!(seen.insert("a"));
+        assert!(!seen.insert("a"));
+        assert!(seen.insert("b"));
+    }
+
+    #[test]
+    fn seen_ids_evicts_oldest_past_capacity() {
+        let mut seen = SeenIds::default();
+        for i in 0..SEEN_ID_CAPACITY {
+            assert!(seen.insert(&i.to_string()));
+        }
+        // One past capacity evicts "0", so it's treated as new again.
+        assert!(seen.insert(&SEEN_ID_CAPACITY.to_string()));
+        // Re-inserting "0" in turn evicts "1", the oldest remaining id.
+        assert!(seen.insert("0"));
+        // Still-recent ids remain known.
+        assert!(!seen.insert("2"));
+    }
+
+    #[test]
+    fn pending_actions_evicts_oldest_past_capacity() {
+        let mut pending = PendingActions::default();
+        for i in 0..PENDING_ACTIONS_CAPACITY {
+            pending.insert(i as i32, vec![]);
+        }
+        pending.insert(PENDING_ACTIONS_CAPACITY as i32, vec![]);
+        assert!(pending.remove(0).is_none());
+        assert!(pending.remove(1).is_some());
+    }
+
+    #[test]
+    fn saved_subscription_round_trips_without_auth() {
+        let saved = SavedSubscription {
+            server_url: "https://ntfy.sh".to_string(),
+            topic: "alerts".to_string(),
+            min_priority: 3,
+        };
+        let json = serde_json::to_string(&saved).unwrap();
+        assert!(!json.contains("auth"));
+        let restored: SavedSubscription = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.server_url, saved.server_url);
+        assert_eq!(restored.topic, saved.topic);
+        assert_eq!(restored.min_priority, saved.min_priority);
+    }
+}